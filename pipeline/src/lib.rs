@@ -0,0 +1,101 @@
+//! Pure, non-wasm parsing and merging logic for the tracker pipeline.
+//!
+//! Split out of `tracker-collector` into its own crate so that native
+//! tooling (e.g. `xtask bench`) can depend on just this hot path without
+//! pulling in `worker`/`wasm_bindgen`/`tracing-web`/`tokio-tungstenite-wasm`
+//! and the rest of the wasm-facing dependency graph.
+
+use dashmap::DashSet;
+use std::fmt;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct Trackers {
+    pub trackers: DashSet<String>,
+}
+
+/// A tracker-source body matched none of the formats `parse_tracker`
+/// understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidTrackerFormat;
+
+impl fmt::Display for InvalidTrackerFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid tracker list format")
+    }
+}
+
+impl std::error::Error for InvalidTrackerFormat {}
+
+/// Parses a tracker-source fixture in any of the formats the collector
+/// accepts: a JSON `{"trackers": [...]}` document, a comma-separated list,
+/// or a blank-line (`\n\n`) separated list.
+///
+/// A source that returns something else is just a bad/unreachable source,
+/// not a bug in the collector, so callers are expected to treat `Err` the
+/// same way they treat a fetch failure rather than unwrapping it.
+pub fn parse_tracker(trackers_list: &str) -> Result<DashSet<String>, InvalidTrackerFormat> {
+    if let Ok(trackers) = serde_json::from_str::<Trackers>(trackers_list) {
+        return Ok(trackers.trackers);
+    };
+    if trackers_list.contains(',') {
+        return Ok(trackers_list
+            .split(',')
+            .map(|tracker| tracker.to_string())
+            .collect());
+    }
+    if trackers_list.contains("\n\n") {
+        return Ok(trackers_list
+            .split("\n\n")
+            .map(|tracker| tracker.to_string())
+            .collect());
+    }
+    Err(InvalidTrackerFormat)
+}
+
+/// Dedup-merges `new` into `existing`, returning how many entries were
+/// actually new (i.e. the entries that survived deduplication).
+pub fn merge(existing: &DashSet<String>, new: DashSet<String>) -> usize {
+    let before = existing.len();
+    existing.extend(new);
+    existing.len() - before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_json_format() {
+        let parsed = parse_tracker(r#"{"trackers": ["udp://a.test:80/announce"]}"#).unwrap();
+        assert!(parsed.contains("udp://a.test:80/announce"));
+    }
+
+    #[test]
+    fn parses_comma_separated_format() {
+        let parsed = parse_tracker("udp://a.test:80/announce,udp://b.test:80/announce").unwrap();
+        assert_eq!(parsed.len(), 2);
+    }
+
+    #[test]
+    fn parses_blank_line_separated_format() {
+        let parsed =
+            parse_tracker("udp://a.test:80/announce\n\nudp://b.test:80/announce").unwrap();
+        assert_eq!(parsed.len(), 2);
+    }
+
+    #[test]
+    fn rejects_unrecognized_format() {
+        assert_eq!(
+            parse_tracker("just a single plain line with no delimiter"),
+            Err(InvalidTrackerFormat)
+        );
+    }
+
+    #[test]
+    fn merge_reports_only_new_entries() {
+        let existing: DashSet<String> = ["a".to_string()].into_iter().collect();
+        let new: DashSet<String> = ["a".to_string(), "b".to_string()].into_iter().collect();
+        assert_eq!(merge(&existing, new), 1);
+        assert_eq!(existing.len(), 2);
+    }
+}
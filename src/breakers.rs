@@ -0,0 +1,204 @@
+use chrono::Utc;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use worker::Env;
+
+const KV_BINDING: &str = "METRICS";
+const KV_KEY_PREFIX: &str = "breaker:";
+
+/// Number of consecutive failures before a source is tripped open.
+const FAILURE_THRESHOLD: u32 = 3;
+/// Base backoff, in milliseconds, used for the exponential backoff once a
+/// breaker trips.
+const BASE_BACKOFF_MS: i64 = 30_000;
+/// Upper bound on how long a breaker can stay tripped, in milliseconds.
+const MAX_BACKOFF_MS: i64 = 60 * 60 * 1_000;
+/// KV entries are given this TTL so a source that recovers (or is removed
+/// from `trackers.yml`) doesn't leave a breaker entry behind forever.
+const KV_ENTRY_TTL_SECS: u64 = (MAX_BACKOFF_MS / 1_000) as u64;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct Breaker {
+    consecutive_failures: u32,
+    tripped_until_unix_ms: Option<i64>,
+}
+
+impl Breaker {
+    /// Counts one more consecutive failure and, once `FAILURE_THRESHOLD` is
+    /// reached, trips the breaker until `now_unix_ms` plus the backoff.
+    fn record_failure(&mut self, now_unix_ms: i64) {
+        self.consecutive_failures += 1;
+        if self.consecutive_failures >= FAILURE_THRESHOLD {
+            let exponent = (self.consecutive_failures - FAILURE_THRESHOLD).min(31);
+            let backoff_ms = BASE_BACKOFF_MS
+                .saturating_mul(1i64 << exponent)
+                .min(MAX_BACKOFF_MS);
+            self.tripped_until_unix_ms = Some(now_unix_ms + backoff_ms);
+        }
+    }
+}
+
+/// Per-authority circuit breaker guarding the remote tracker-list fetches.
+///
+/// Keyed by URL authority (`host[:port]`) and persisted in Workers KV, so
+/// that a dead source stops being re-hit on every scheduled run once it has
+/// failed enough times in a row — `scheduled`/`fetch` each run in their own
+/// stateless invocation, so an in-memory-only breaker would reset itself
+/// every tick.
+#[derive(Debug, Clone)]
+pub struct Breakers {
+    kv: Option<worker::kv::KvStore>,
+    /// Caches reads/writes within a single invocation so fetching several
+    /// trackers from the same host only hits KV once.
+    cache: Arc<DashMap<String, Breaker>>,
+}
+
+impl Breakers {
+    pub fn new(env: &Env) -> Self {
+        Self {
+            kv: env.kv(KV_BINDING).ok(),
+            cache: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Returns `true` when `url` has no breaker entry yet, or its cooldown
+    /// has elapsed. URLs with no parseable authority are always allowed
+    /// through (there is nothing to key a breaker on).
+    pub async fn should_try(&self, url: &str) -> bool {
+        let Some(authority) = authority_of(url) else {
+            return true;
+        };
+        let breaker = self.load(&authority).await;
+        match breaker.tripped_until_unix_ms {
+            Some(until) => Utc::now().timestamp_millis() >= until,
+            None => true,
+        }
+    }
+
+    /// Records a failed fetch, tripping the breaker with an exponential
+    /// backoff (capped at `MAX_BACKOFF_MS`) once `FAILURE_THRESHOLD`
+    /// consecutive failures have been seen.
+    pub async fn fail(&self, url: &str) {
+        let Some(authority) = authority_of(url) else {
+            return;
+        };
+        let mut breaker = self.load(&authority).await;
+        breaker.record_failure(Utc::now().timestamp_millis());
+        self.store(&authority, &breaker).await;
+    }
+
+    /// Resets the breaker for `url` after a successful fetch.
+    pub async fn succeed(&self, url: &str) {
+        let Some(authority) = authority_of(url) else {
+            return;
+        };
+        self.cache.remove(&authority);
+        if let Some(kv) = &self.kv {
+            let _ = kv.delete(&kv_key(&authority)).await;
+        }
+    }
+
+    async fn load(&self, authority: &str) -> Breaker {
+        if let Some(breaker) = self.cache.get(authority) {
+            return breaker.clone();
+        }
+        let breaker = match &self.kv {
+            Some(kv) => kv
+                .get(&kv_key(authority))
+                .json::<Breaker>()
+                .await
+                .ok()
+                .flatten()
+                .unwrap_or_default(),
+            None => Breaker::default(),
+        };
+        self.cache.insert(authority.to_string(), breaker.clone());
+        breaker
+    }
+
+    async fn store(&self, authority: &str, breaker: &Breaker) {
+        self.cache.insert(authority.to_string(), breaker.clone());
+        let Some(kv) = &self.kv else {
+            return;
+        };
+        let Ok(put) = kv.put(&kv_key(authority), breaker) else {
+            return;
+        };
+        let _ = put.expiration_ttl(KV_ENTRY_TTL_SECS).execute().await;
+    }
+}
+
+fn kv_key(authority: &str) -> String {
+    format!("{KV_KEY_PREFIX}{authority}")
+}
+
+/// Extracts the `host[:port]` authority from a URL, without pulling in a
+/// full URL-parsing dependency for this one use site.
+fn authority_of(url: &str) -> Option<String> {
+    let after_scheme = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let authority = after_scheme
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or("")
+        .trim();
+    if authority.is_empty() {
+        None
+    } else {
+        Some(authority.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn authority_of_strips_scheme_and_path() {
+        assert_eq!(
+            authority_of("udp://tracker.example.test:80/announce"),
+            Some("tracker.example.test:80".to_string())
+        );
+    }
+
+    #[test]
+    fn authority_of_handles_schemeless_urls() {
+        assert_eq!(
+            authority_of("tracker.example.test/announce"),
+            Some("tracker.example.test".to_string())
+        );
+    }
+
+    #[test]
+    fn authority_of_skips_unparseable_urls() {
+        assert_eq!(authority_of(""), None);
+        assert_eq!(authority_of("://"), None);
+    }
+
+    #[test]
+    fn breaker_does_not_trip_before_threshold() {
+        let mut breaker = Breaker::default();
+        for _ in 0..FAILURE_THRESHOLD - 1 {
+            breaker.record_failure(0);
+        }
+        assert_eq!(breaker.consecutive_failures, FAILURE_THRESHOLD - 1);
+        assert!(breaker.tripped_until_unix_ms.is_none());
+    }
+
+    #[test]
+    fn backoff_doubles_and_caps_at_max() {
+        // Mirrors the backoff computation in `Breakers::fail` directly,
+        // since that method is async/KV-backed and not itself a pure
+        // function to call from a sync test.
+        let backoff_for = |consecutive_failures: u32| {
+            let exponent = (consecutive_failures - FAILURE_THRESHOLD).min(31);
+            BASE_BACKOFF_MS
+                .saturating_mul(1i64 << exponent)
+                .min(MAX_BACKOFF_MS)
+        };
+
+        assert_eq!(backoff_for(FAILURE_THRESHOLD), BASE_BACKOFF_MS);
+        assert_eq!(backoff_for(FAILURE_THRESHOLD + 1), BASE_BACKOFF_MS * 2);
+        assert_eq!(backoff_for(FAILURE_THRESHOLD + 20), MAX_BACKOFF_MS);
+    }
+}
@@ -0,0 +1,97 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use worker::{Delay, Env, Response, Result};
+
+const KV_BINDING: &str = "METRICS";
+const EVENT_KEY: &str = "last_event";
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceOutcome {
+    pub url: String,
+    pub tracker_count: usize,
+    pub succeeded: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushOutcome {
+    pub target: String,
+    pub succeeded: bool,
+}
+
+/// Everything that happened during one `scheduled` collection cycle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionEvent {
+    pub sequence: u64,
+    pub timestamp_unix_ms: i64,
+    pub sources: Vec<SourceOutcome>,
+    pub total_after_dedup: usize,
+    pub pushes: Vec<PushOutcome>,
+}
+
+impl CollectionEvent {
+    /// Persists the outcome of a collection cycle to KV, where `stream`
+    /// picks it up on its next poll. Silently no-ops if the KV namespace
+    /// isn't bound (unlike `Metrics::store`, which returns an error).
+    pub async fn record(
+        env: &Env,
+        sources: Vec<SourceOutcome>,
+        total_after_dedup: usize,
+        pushes: Vec<PushOutcome>,
+    ) -> Result<()> {
+        let Ok(kv) = env.kv(KV_BINDING) else {
+            return Ok(());
+        };
+        let previous_sequence = kv
+            .get(EVENT_KEY)
+            .json::<CollectionEvent>()
+            .await
+            .ok()
+            .flatten()
+            .map(|event| event.sequence)
+            .unwrap_or(0);
+
+        let event = CollectionEvent {
+            sequence: previous_sequence + 1,
+            timestamp_unix_ms: Utc::now().timestamp_millis(),
+            sources,
+            total_after_dedup,
+            pushes,
+        };
+        kv.put(EVENT_KEY, &event)?.execute().await?;
+        Ok(())
+    }
+}
+
+/// Builds an SSE response that polls KV for the latest `CollectionEvent`
+/// and frames it as `event: collection` / `data: ...`, falling back to a
+/// `: keep-alive` comment when nothing new has landed since the last poll.
+pub fn stream(env: Env) -> Result<Response> {
+    let body = futures::stream::unfold((env, 0u64), |(env, last_sent)| async move {
+        Delay::from(POLL_INTERVAL).await;
+        let (frame, sequence) = match latest_event(&env).await {
+            Some(event) if event.sequence > last_sent => {
+                let data = serde_json::to_string(&event).unwrap_or_default();
+                (
+                    format!("event: collection\ndata: {data}\n\n"),
+                    event.sequence,
+                )
+            }
+            _ => (": keep-alive\n\n".to_string(), last_sent),
+        };
+        Some((Ok(frame.into_bytes()), (env, sequence)))
+    });
+
+    let mut response = Response::from_stream(body)?;
+    response
+        .headers_mut()
+        .set("Content-Type", "text/event-stream")?;
+    response.headers_mut().set("Cache-Control", "no-cache")?;
+    Ok(response)
+}
+
+async fn latest_event(env: &Env) -> Option<CollectionEvent> {
+    let kv = env.kv(KV_BINDING).ok()?;
+    kv.get(EVENT_KEY).json::<CollectionEvent>().await.ok()?
+}
@@ -1,20 +1,26 @@
+use breakers::Breakers;
 use dashmap::DashSet;
-use futures::{SinkExt, StreamExt};
+use events::{CollectionEvent, PushOutcome, SourceOutcome};
+use metrics::Metrics;
+use push::{parse_targets, push_all, PushTarget};
+use retry::{fetch_with_retry, RetryConfig};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use tracing::info;
+use tracker_pipeline::{parse_tracker, Trackers};
+
+mod breakers;
+mod events;
+mod metrics;
+mod push;
+mod retry;
 use tracing_subscriber::{
     fmt::{format::Pretty, time::UtcTime},
     prelude::*,
 };
 use tracing_web::{performance_layer, MakeConsoleWriter};
-use wasm_bindgen::JsValue;
 use worker::*;
 
-#[derive(serde::Deserialize, Debug)]
-struct Trackers {
-    trackers: DashSet<String>,
-}
-
 #[event(start)]
 fn start() {
     console_error_panic_hook::set_once();
@@ -31,9 +37,22 @@ fn start() {
 }
 
 #[event(fetch)]
-async fn fetch(req: HttpRequest, _env: Env, _ctx: Context) -> Result<Response> {
+async fn fetch(req: HttpRequest, env: Env, _ctx: Context) -> Result<Response> {
+    if req.uri().path() == "/metrics" {
+        let metrics = Metrics::load(&env).await;
+        let mut response = Response::ok(metrics.to_openmetrics())?;
+        response
+            .headers_mut()
+            .set("Content-Type", "application/openmetrics-text; version=1.0.0")?;
+        return Ok(response);
+    }
+
+    if req.uri().path() == "/events" {
+        return events::stream(env);
+    }
+
     let spilter = if req.uri().path() == "/" { "," } else { "\n\n" };
-    let trackers = get_trackers().await;
+    let (trackers, _stats, _outcomes) = get_trackers(&env).await;
 
     let result = Vec::from(
         trackers
@@ -53,42 +72,58 @@ async fn scheduled(_event: ScheduledEvent, env: Env, _ctx: ScheduleContext) {
         .secret("ARIA2_URL")
         .expect("ARIA2_URL secret not found")
         .to_string();
-    let secret_key = env
-        .secret("SECRET_KEY")
-        .expect("SECRET_KEY secret not found")
-        .to_string();
 
-    let trackers = get_trackers().await;
+    let (trackers, stats, source_outcomes) = get_trackers(&env).await;
 
     info!("Total trackers: {}", trackers.len());
 
-    let trackers = Vec::from(
-        trackers
-            .iter()
-            .map(|tracker| tracker.clone())
-            .collect::<Vec<String>>(),
-    )
-    .join(",");
-
-    let pay_load = serde_json::json!({
-        "jsonrpc": "2.0",
-        "method": "aria2.changeGlobalOption",
-        "id": "cron",
-        "params": [
-            format!("token:{}", secret_key),
-            {
-                "bt-tracker": trackers
-            }
-        ]
-    });
-    if aria2_url.starts_with("http") {
-        change_global_option_http(aria2_url, pay_load).await;
-    } else {
-        change_global_option_ws(aria2_url, pay_load).await;
+    let tracker_strings: Vec<String> = trackers.iter().map(|tracker| tracker.clone()).collect();
+    let mut metrics = Metrics {
+        total_trackers: trackers.len(),
+        sources_attempted: stats.attempted,
+        sources_failed: stats.failed,
+        trackers_by_scheme: Metrics::count_schemes(tracker_strings.iter()),
+        sources: Metrics::count_sources(&source_outcomes),
+        ..Metrics::load(&env).await
+    };
+
+    let trackers = tracker_strings.join(",");
+
+    let targets = parse_targets(&aria2_url);
+    let results = push_all(&env, &targets, &trackers).await;
+    let mut push_outcomes = Vec::with_capacity(targets.len());
+    for (target, result) in targets.iter().zip(results) {
+        if matches!(target, PushTarget::Aria2Http(_) | PushTarget::Aria2Ws(_)) {
+            metrics.record_aria2_push(result.is_ok());
+        }
+        push_outcomes.push(PushOutcome {
+            target: format!("{target:?}"),
+            succeeded: result.is_ok(),
+        });
+        if let Err(err) = result {
+            tracing::warn!("Failed to push trackers to {target:?}: {err}");
+        }
+    }
+
+    if let Err(err) = metrics.store(&env).await {
+        tracing::warn!("Failed to persist metrics: {err}");
+    }
+    if let Err(err) =
+        CollectionEvent::record(&env, source_outcomes, tracker_strings.len(), push_outcomes).await
+    {
+        tracing::warn!("Failed to record collection event: {err}");
     }
 }
 
-async fn get_trackers() -> DashSet<String> {
+/// How many remote sources `get_trackers` attempted and how many of those
+/// attempts ultimately failed (after breaker skips and retries).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SourceStats {
+    pub attempted: usize,
+    pub failed: usize,
+}
+
+async fn get_trackers(env: &Env) -> (DashSet<String>, SourceStats, Vec<SourceOutcome>) {
     tracing::info!("Fetching trackers");
     let trackers: Trackers =
         serde_yaml::from_str(include_str!("../trackers.yml")).expect("Failed to parse trackers");
@@ -98,19 +133,55 @@ async fn get_trackers() -> DashSet<String> {
         .into_iter()
         .partition(|tracker| tracker.ends_with("announce"));
     let trackers_set: DashSet<String> = trackers_vec.into_iter().collect();
-    let requests: Vec<Request> = request
-        .iter()
-        .map(|tracker| Request::new(&tracker, Method::Get).expect("Failed to create request"))
-        .collect();
+    let breakers = Breakers::new(env);
+    let retry_config = RetryConfig::from_env(env);
     let trackers_set = Arc::new(Mutex::new(trackers_set));
+    let attempted = request.len();
+    let failed = Arc::new(AtomicUsize::new(0));
+    let source_outcomes = Arc::new(Mutex::new(Vec::with_capacity(attempted)));
     let mut tasks = Vec::new();
-    requests.into_iter().for_each(|request| {
+    request.into_iter().for_each(|url| {
         let trackers_set = trackers_set.clone();
+        let breakers = breakers.clone();
+        let failed = failed.clone();
+        let source_outcomes = source_outcomes.clone();
         let task = async move {
-            let mut response = Fetch::Request(request).send().await.unwrap();
-            let text = response.text().await.unwrap();
-            let trackers = parse_tracker(&text);
-            trackers_set.lock().unwrap().extend(trackers);
+            if !breakers.should_try(&url).await {
+                tracing::warn!("Skipping {url}, breaker is tripped");
+                failed.fetch_add(1, Ordering::Relaxed);
+                source_outcomes.lock().unwrap().push(SourceOutcome {
+                    url,
+                    tracker_count: 0,
+                    succeeded: false,
+                });
+                return;
+            }
+            match fetch_with_retry(&url, &retry_config)
+                .await
+                .map_err(|err| err.to_string())
+                .and_then(|text| parse_tracker(&text).map_err(|err| err.to_string()))
+            {
+                Ok(trackers) => {
+                    let tracker_count = trackers.len();
+                    trackers_set.lock().unwrap().extend(trackers);
+                    breakers.succeed(&url).await;
+                    source_outcomes.lock().unwrap().push(SourceOutcome {
+                        url,
+                        tracker_count,
+                        succeeded: true,
+                    });
+                }
+                Err(err) => {
+                    tracing::warn!("Giving up on {url}: {err}");
+                    breakers.fail(&url).await;
+                    failed.fetch_add(1, Ordering::Relaxed);
+                    source_outcomes.lock().unwrap().push(SourceOutcome {
+                        url,
+                        tracker_count: 0,
+                        succeeded: false,
+                    });
+                }
+            }
         };
         tasks.push(task);
     });
@@ -118,93 +189,10 @@ async fn get_trackers() -> DashSet<String> {
     futures::future::join_all(tasks).await;
 
     let trackers = trackers_set.lock().unwrap().clone();
-    trackers
-}
-
-async fn change_global_option_http(aria2_url: String, pay_load: serde_json::Value) {
-    let mut headers = Headers::new();
-    headers.set("Content-Type", "application/json").unwrap();
-    let request = Request::new_with_init(
-        &aria2_url,
-        &RequestInit {
-            method: Method::Post,
-            headers,
-            body: Some(JsValue::from(pay_load.to_string())),
-            ..RequestInit::default()
-        },
-    )
-    .expect("Failed to create request");
-
-    let response = Fetch::Request(request)
-        .send()
-        .await
-        .expect("Failed to send request")
-        .json::<serde_json::Value>()
-        .await
-        .unwrap();
-    match response.get("result") {
-        Some(result) => {
-            info!("Response: {}", result.to_string());
-        }
-        None => {
-            info!("Error: {:#?}", response["error"]);
-        }
-    }
-}
-
-async fn change_global_option_ws(aria2_url: String, pay_load: serde_json::Value) {
-    let ws_stream = tokio_tungstenite_wasm::connect(aria2_url)
-        .await
-        .expect("Failed to connect to websocket");
-
-    let (mut tx, mut rx) = ws_stream.split();
-
-    info!("Connected to websocket");
-
-    let receive_task = async move {
-        while let Some(msg) = rx.next().await {
-            let msg = msg.expect("Failed to receive message");
-            let msg = serde_json::from_str::<serde_json::Value>(&msg.to_string())
-                .expect("Failed to parse message");
-            if Some("cron") == msg.get("id").and_then(|v| v.as_str()) {
-                match msg.get("result") {
-                    Some(result) => {
-                        info!("Result: {:#?}", result);
-                    }
-                    None => {
-                        info!("Error: {:#?}", msg["error"]);
-                    }
-                }
-                break;
-            }
-        }
-    };
-
-    let send_task = async move {
-        tx.send(tokio_tungstenite_wasm::Message::text(pay_load.to_string()))
-            .await
-            .expect("Failed to send message");
-        info!("Message sent!");
+    let stats = SourceStats {
+        attempted,
+        failed: failed.load(Ordering::Relaxed),
     };
-
-    futures::future::join(receive_task, send_task).await;
-}
-
-fn parse_tracker(trackers_list: &str) -> DashSet<String> {
-    if let Ok(trackers) = serde_json::from_str::<Trackers>(&trackers_list) {
-        return trackers.trackers;
-    };
-    if trackers_list.contains(",") {
-        return trackers_list
-            .split(",")
-            .map(|tracker| tracker.to_string())
-            .collect();
-    }
-    if trackers_list.contains("\n\n") {
-        return trackers_list
-            .split("\n\n")
-            .map(|tracker| tracker.to_string())
-            .collect();
-    }
-    panic!("Invalid tracker list format");
+    let source_outcomes = source_outcomes.lock().unwrap().clone();
+    (trackers, stats, source_outcomes)
 }
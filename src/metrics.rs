@@ -0,0 +1,174 @@
+use crate::events::SourceOutcome;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use worker::{Env, Result};
+
+const KV_BINDING: &str = "METRICS";
+const KV_KEY: &str = "metrics";
+
+/// Per-source outcome of the last collection cycle, keyed by source URL.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceMetric {
+    pub tracker_count: usize,
+    pub succeeded: bool,
+}
+
+/// Collection-health metrics, persisted in Workers KV so they survive
+/// between the stateless `scheduled` and `fetch` invocations.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Metrics {
+    pub total_trackers: usize,
+    pub sources_attempted: usize,
+    pub sources_failed: usize,
+    pub trackers_by_scheme: HashMap<String, usize>,
+    pub sources: HashMap<String, SourceMetric>,
+    pub last_aria2_push_unix_ms: Option<i64>,
+    pub last_aria2_push_succeeded: Option<bool>,
+}
+
+impl Metrics {
+    /// Reads the last snapshot written by `scheduled`, or an all-zero
+    /// snapshot if the KV namespace is unbound or nothing has run yet.
+    pub async fn load(env: &Env) -> Self {
+        let Ok(kv) = env.kv(KV_BINDING) else {
+            return Self::default();
+        };
+        kv.get(KV_KEY)
+            .json::<Metrics>()
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+    }
+
+    pub async fn store(&self, env: &Env) -> Result<()> {
+        let kv = env.kv(KV_BINDING)?;
+        kv.put(KV_KEY, self)?.execute().await?;
+        Ok(())
+    }
+
+    /// Records which scheme (`http`, `https`, `udp`, `ws`, ...) each
+    /// tracker in `trackers` uses.
+    pub fn count_schemes<'a>(trackers: impl Iterator<Item = &'a String>) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for tracker in trackers {
+            let scheme = tracker
+                .split_once("://")
+                .map(|(scheme, _)| scheme)
+                .unwrap_or("unknown");
+            *counts.entry(scheme.to_string()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Builds the per-source breakdown for `Metrics::sources` from a
+    /// collection cycle's `SourceOutcome`s, so `/metrics` can show which
+    /// source is failing instead of just an aggregate failure count.
+    pub fn count_sources(outcomes: &[SourceOutcome]) -> HashMap<String, SourceMetric> {
+        outcomes
+            .iter()
+            .map(|outcome| {
+                (
+                    outcome.url.clone(),
+                    SourceMetric {
+                        tracker_count: outcome.tracker_count,
+                        succeeded: outcome.succeeded,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    pub fn record_aria2_push(&mut self, succeeded: bool) {
+        self.last_aria2_push_unix_ms = Some(Utc::now().timestamp_millis());
+        self.last_aria2_push_succeeded = Some(succeeded);
+    }
+
+    /// Renders the snapshot as OpenMetrics/Prometheus exposition text.
+    pub fn to_openmetrics(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str("# TYPE tracker_collector_trackers_total gauge\n");
+        out.push_str(&format!(
+            "tracker_collector_trackers_total {}\n",
+            self.total_trackers
+        ));
+
+        out.push_str("# TYPE tracker_collector_sources_attempted gauge\n");
+        out.push_str(&format!(
+            "tracker_collector_sources_attempted {}\n",
+            self.sources_attempted
+        ));
+
+        out.push_str("# TYPE tracker_collector_sources_failed gauge\n");
+        out.push_str(&format!(
+            "tracker_collector_sources_failed {}\n",
+            self.sources_failed
+        ));
+
+        out.push_str("# TYPE tracker_collector_trackers_by_scheme gauge\n");
+        for (scheme, count) in &self.trackers_by_scheme {
+            out.push_str(&format!(
+                "tracker_collector_trackers_by_scheme{{scheme=\"{scheme}\"}} {count}\n"
+            ));
+        }
+
+        out.push_str("# TYPE tracker_collector_source_up gauge\n");
+        for (source, metric) in &self.sources {
+            out.push_str(&format!(
+                "tracker_collector_source_up{{source=\"{source}\"}} {}\n",
+                metric.succeeded as u8
+            ));
+        }
+
+        out.push_str("# TYPE tracker_collector_source_trackers gauge\n");
+        for (source, metric) in &self.sources {
+            out.push_str(&format!(
+                "tracker_collector_source_trackers{{source=\"{source}\"}} {}\n",
+                metric.tracker_count
+            ));
+        }
+
+        if let Some(ts) = self.last_aria2_push_unix_ms {
+            out.push_str("# TYPE tracker_collector_last_aria2_push_timestamp_ms gauge\n");
+            out.push_str(&format!(
+                "tracker_collector_last_aria2_push_timestamp_ms {ts}\n"
+            ));
+        }
+        if let Some(succeeded) = self.last_aria2_push_succeeded {
+            out.push_str("# TYPE tracker_collector_last_aria2_push_success gauge\n");
+            out.push_str(&format!(
+                "tracker_collector_last_aria2_push_success {}\n",
+                succeeded as u8
+            ));
+        }
+
+        out.push_str("# EOF\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_schemes_groups_by_scheme() {
+        let trackers = vec![
+            "udp://a.test:80/announce".to_string(),
+            "udp://b.test:80/announce".to_string(),
+            "http://c.test/announce".to_string(),
+        ];
+        let counts = Metrics::count_schemes(trackers.iter());
+        assert_eq!(counts.get("udp"), Some(&2));
+        assert_eq!(counts.get("http"), Some(&1));
+    }
+
+    #[test]
+    fn count_schemes_falls_back_to_unknown_without_a_scheme() {
+        let trackers = vec!["not-a-url".to_string()];
+        let counts = Metrics::count_schemes(trackers.iter());
+        assert_eq!(counts.get("unknown"), Some(&1));
+    }
+}
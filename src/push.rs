@@ -0,0 +1,332 @@
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tracing::{info, warn};
+use wasm_bindgen::JsValue;
+use worker::{Env, Error, Fetch, Headers, Method, Request, RequestInit, Result};
+
+/// A single place to push the collected tracker list to.
+#[derive(Debug, Clone)]
+pub enum PushTarget {
+    Aria2Http(String),
+    Aria2Ws(String),
+    Qbittorrent(String),
+}
+
+/// Parses the comma-separated `ARIA2_URL` secret into individual targets.
+///
+/// Entries prefixed with `qbittorrent+` are treated as a qBittorrent WebUI
+/// base URL (prefix stripped); everything else is an aria2 endpoint, split
+/// between JSON-RPC over HTTP(S) and over `ws(s)://`.
+pub fn parse_targets(aria2_url: &str) -> Vec<PushTarget> {
+    aria2_url
+        .split(',')
+        .map(str::trim)
+        .filter(|endpoint| !endpoint.is_empty())
+        .map(|endpoint| {
+            if let Some(base_url) = endpoint.strip_prefix("qbittorrent+") {
+                PushTarget::Qbittorrent(base_url.to_string())
+            } else if endpoint.starts_with("ws://") || endpoint.starts_with("wss://") {
+                PushTarget::Aria2Ws(endpoint.to_string())
+            } else {
+                PushTarget::Aria2Http(endpoint.to_string())
+            }
+        })
+        .collect()
+}
+
+/// Pushes `trackers` to every target concurrently, returning one
+/// `Result<()>` per target in the same order as `targets`.
+pub async fn push_all(env: &Env, targets: &[PushTarget], trackers: &str) -> Vec<Result<()>> {
+    let needs_aria2_secret = targets
+        .iter()
+        .any(|target| matches!(target, PushTarget::Aria2Http(_) | PushTarget::Aria2Ws(_)));
+    let secret_key = needs_aria2_secret.then(|| {
+        env.secret("SECRET_KEY")
+            .expect("SECRET_KEY secret not found")
+            .to_string()
+    });
+    let use_multicall = env
+        .var("ARIA2_USE_MULTICALL")
+        .map(|v| v.to_string() == "true")
+        .unwrap_or(false);
+
+    let tasks = targets.iter().map(|target| async {
+        match target {
+            PushTarget::Aria2Http(url) => {
+                let secret_key = secret_key.as_deref().expect("aria2 target requires SECRET_KEY");
+                push_aria2_http(url, secret_key, trackers, use_multicall).await
+            }
+            PushTarget::Aria2Ws(url) => {
+                let secret_key = secret_key.as_deref().expect("aria2 target requires SECRET_KEY");
+                push_aria2_ws(url, secret_key, trackers).await
+            }
+            PushTarget::Qbittorrent(base_url) => push_qbittorrent(env, base_url, trackers).await,
+        }
+    });
+
+    futures::future::join_all(tasks).await
+}
+
+async fn post_json(url: &str, payload: Value) -> Result<Value> {
+    let mut headers = Headers::new();
+    headers.set("Content-Type", "application/json")?;
+    let request = Request::new_with_init(
+        url,
+        &RequestInit {
+            method: Method::Post,
+            headers,
+            body: Some(JsValue::from(payload.to_string())),
+            ..RequestInit::default()
+        },
+    )?;
+
+    Fetch::Request(request).send().await?.json::<Value>().await
+}
+
+/// Fetches the gids of currently active downloads so their `bt-tracker`
+/// option can be updated alongside the global default.
+async fn tell_active_gids(url: &str, secret_key: &str) -> Result<Vec<String>> {
+    let payload = json!({
+        "jsonrpc": "2.0",
+        "method": "aria2.tellActive",
+        "id": "cron",
+        "params": [format!("token:{secret_key}"), ["gid"]]
+    });
+    let response = post_json(url, payload).await?;
+    let gids = response
+        .get("result")
+        .and_then(Value::as_array)
+        .map(|downloads| {
+            downloads
+                .iter()
+                .filter_map(|download| download.get("gid")?.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(gids)
+}
+
+async fn push_aria2_http(
+    url: &str,
+    secret_key: &str,
+    trackers: &str,
+    use_multicall: bool,
+) -> Result<()> {
+    let payload = if use_multicall {
+        let active_gids = tell_active_gids(url, secret_key).await.unwrap_or_else(|err| {
+            warn!("Failed to list active downloads on {url}: {err}");
+            Vec::new()
+        });
+
+        let mut calls = vec![json!({
+            "methodName": "aria2.changeGlobalOption",
+            "params": [format!("token:{secret_key}"), { "bt-tracker": trackers }]
+        })];
+        calls.extend(active_gids.into_iter().map(|gid| {
+            json!({
+                "methodName": "aria2.changeOption",
+                "params": [format!("token:{secret_key}"), gid, { "bt-tracker": trackers }]
+            })
+        }));
+
+        json!({
+            "jsonrpc": "2.0",
+            "method": "system.multicall",
+            "id": "cron",
+            "params": [calls]
+        })
+    } else {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "aria2.changeGlobalOption",
+            "id": "cron",
+            "params": [
+                format!("token:{secret_key}"),
+                { "bt-tracker": trackers }
+            ]
+        })
+    };
+
+    let response = post_json(url, payload).await?;
+    match response.get("result") {
+        Some(result) => {
+            info!("{url}: {result}");
+            Ok(())
+        }
+        None => {
+            let error = response["error"].clone();
+            warn!("{url}: {error:#?}");
+            Err(Error::RustError(format!("aria2 RPC error from {url}: {error}")))
+        }
+    }
+}
+
+async fn push_aria2_ws(url: &str, secret_key: &str, trackers: &str) -> Result<()> {
+    let ws_stream = tokio_tungstenite_wasm::connect(url)
+        .await
+        .map_err(|err| Error::RustError(format!("Failed to connect to {url}: {err}")))?;
+
+    let (mut tx, mut rx) = ws_stream.split();
+
+    let payload = json!({
+        "jsonrpc": "2.0",
+        "method": "aria2.changeGlobalOption",
+        "id": "cron",
+        "params": [
+            format!("token:{secret_key}"),
+            { "bt-tracker": trackers }
+        ]
+    });
+
+    tx.send(tokio_tungstenite_wasm::Message::text(payload.to_string()))
+        .await
+        .map_err(|err| Error::RustError(format!("Failed to send message to {url}: {err}")))?;
+
+    while let Some(msg) = rx.next().await {
+        let msg = msg.map_err(|err| Error::RustError(format!("{url}: {err}")))?;
+        let msg: Value = serde_json::from_str(&msg.to_string())
+            .map_err(|err| Error::RustError(format!("{url}: {err}")))?;
+        if Some("cron") != msg.get("id").and_then(Value::as_str) {
+            continue;
+        }
+        return match msg.get("result") {
+            Some(result) => {
+                info!("{url}: {result}");
+                Ok(())
+            }
+            None => {
+                let error = msg["error"].clone();
+                warn!("{url}: {error:#?}");
+                Err(Error::RustError(format!("aria2 RPC error from {url}: {error}")))
+            }
+        };
+    }
+
+    Err(Error::RustError(format!(
+        "{url}: websocket closed before a response was received"
+    )))
+}
+
+async fn push_qbittorrent(env: &Env, base_url: &str, trackers: &str) -> Result<()> {
+    let username = env
+        .secret("QBITTORRENT_USERNAME")
+        .expect("QBITTORRENT_USERNAME secret not found")
+        .to_string();
+    let password = env
+        .secret("QBITTORRENT_PASSWORD")
+        .expect("QBITTORRENT_PASSWORD secret not found")
+        .to_string();
+
+    let sid = qbittorrent_login(base_url, &username, &password).await?;
+
+    let mut headers = Headers::new();
+    headers.set("Content-Type", "application/x-www-form-urlencoded")?;
+    headers.set("Cookie", &format!("SID={sid}"))?;
+    // qBittorrent's `bt_trackers` preference is newline-separated, unlike the
+    // comma-joined string aria2's `bt-tracker` option expects.
+    let trackers = trackers.replace(',', "\n");
+    let body = format!(
+        "json={}",
+        urlencode(&json!({ "bt_trackers": trackers }).to_string())
+    );
+    let request = Request::new_with_init(
+        &format!("{base_url}/api/v2/app/setPreferences"),
+        &RequestInit {
+            method: Method::Post,
+            headers,
+            body: Some(JsValue::from(body)),
+            ..RequestInit::default()
+        },
+    )?;
+
+    let response = Fetch::Request(request).send().await?;
+    if response.status_code() == 200 {
+        info!("{base_url}: preferences updated");
+        Ok(())
+    } else {
+        Err(Error::RustError(format!(
+            "{base_url}: setPreferences returned status {}",
+            response.status_code()
+        )))
+    }
+}
+
+async fn qbittorrent_login(base_url: &str, username: &str, password: &str) -> Result<String> {
+    let mut headers = Headers::new();
+    headers.set("Content-Type", "application/x-www-form-urlencoded")?;
+    let body = format!(
+        "username={}&password={}",
+        urlencode(username),
+        urlencode(password)
+    );
+    let request = Request::new_with_init(
+        &format!("{base_url}/api/v2/auth/login"),
+        &RequestInit {
+            method: Method::Post,
+            headers,
+            body: Some(JsValue::from(body)),
+            ..RequestInit::default()
+        },
+    )?;
+
+    let response = Fetch::Request(request).send().await?;
+    let cookie = response
+        .headers()
+        .get("Set-Cookie")?
+        .and_then(|set_cookie| {
+            set_cookie
+                .split(';')
+                .next()
+                .and_then(|pair| pair.strip_prefix("SID="))
+                .map(str::to_string)
+        })
+        .ok_or_else(|| Error::RustError(format!("{base_url}: login did not return a SID cookie")))?;
+
+    Ok(cookie)
+}
+
+/// Minimal `application/x-www-form-urlencoded` percent-encoding; avoids
+/// pulling in a dedicated crate for the handful of characters qBittorrent's
+/// API needs escaped.
+fn urlencode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_targets_splits_by_scheme_and_prefix() {
+        let targets = parse_targets(
+            "http://aria2.test/jsonrpc,ws://aria2.test/jsonrpc,qbittorrent+http://qbit.test",
+        );
+        assert!(matches!(targets[0], PushTarget::Aria2Http(ref url) if url == "http://aria2.test/jsonrpc"));
+        assert!(matches!(targets[1], PushTarget::Aria2Ws(ref url) if url == "ws://aria2.test/jsonrpc"));
+        assert!(matches!(targets[2], PushTarget::Qbittorrent(ref url) if url == "http://qbit.test"));
+    }
+
+    #[test]
+    fn parse_targets_trims_whitespace_and_skips_empty_entries() {
+        let targets = parse_targets(" http://a.test/jsonrpc , , http://b.test/jsonrpc ");
+        assert_eq!(targets.len(), 2);
+    }
+
+    #[test]
+    fn urlencode_leaves_unreserved_characters_alone() {
+        assert_eq!(urlencode("abc123-_.~"), "abc123-_.~");
+    }
+
+    #[test]
+    fn urlencode_percent_encodes_everything_else() {
+        assert_eq!(urlencode("a b&c"), "a%20b%26c");
+    }
+}
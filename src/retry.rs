@@ -0,0 +1,130 @@
+use chrono::Utc;
+use rand::Rng;
+use std::time::Duration;
+use worker::{Delay, Env, Error, Fetch, Method, Request};
+
+/// Tunables for [`fetch_with_retry`], sourced from `Env` so operators can
+/// adjust them without a redeploy.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryConfig {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub attempt_timeout: Duration,
+}
+
+impl RetryConfig {
+    pub fn from_env(env: &Env) -> Self {
+        Self {
+            max_attempts: env_u64(env, "FETCH_MAX_ATTEMPTS").unwrap_or(3).max(1) as u32,
+            base_delay: Duration::from_millis(env_u64(env, "FETCH_BASE_DELAY_MS").unwrap_or(500)),
+            attempt_timeout: Duration::from_millis(
+                env_u64(env, "FETCH_TIMEOUT_MS").unwrap_or(5_000),
+            ),
+        }
+    }
+}
+
+/// Reads `key` from either vars or secrets, whichever is bound, and parses
+/// it as a `u64`. Returns `None` on any error, leaving the default to the
+/// caller.
+fn env_u64(env: &Env, key: &str) -> Option<u64> {
+    env.var(key)
+        .map(|v| v.to_string())
+        .or_else(|_| env.secret(key).map(|v| v.to_string()))
+        .ok()
+        .and_then(|v| v.parse().ok())
+}
+
+/// Fetches `url` with up to `config.max_attempts` tries, each bounded by
+/// `config.attempt_timeout` and separated by an exponential backoff with
+/// jitter. Logs the attempt count, outcome and elapsed time of each try so
+/// flaky/slow sources are visible in `tracing`.
+pub async fn fetch_with_retry(url: &str, config: &RetryConfig) -> Result<String, Error> {
+    let mut last_err = None;
+    for attempt in 1..=config.max_attempts {
+        let attempt_started = Utc::now();
+        let outcome = run_attempt(url, config.attempt_timeout).await;
+        let elapsed_ms = (Utc::now() - attempt_started).num_milliseconds();
+
+        match outcome {
+            Ok(text) => {
+                tracing::info!(url, attempt, elapsed_ms, "fetch succeeded");
+                return Ok(text);
+            }
+            Err(err) => {
+                tracing::warn!(
+                    url,
+                    attempt,
+                    max_attempts = config.max_attempts,
+                    elapsed_ms,
+                    error = %err,
+                    "fetch attempt failed"
+                );
+                last_err = Some(err);
+                if attempt < config.max_attempts {
+                    Delay::from(backoff_with_jitter(config.base_delay, attempt)).await;
+                }
+            }
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| Error::RustError("fetch retry loop ran zero times".into())))
+}
+
+async fn run_attempt(url: &str, timeout: Duration) -> Result<String, Error> {
+    let request = Request::new(url, Method::Get)?;
+    let fetch = async {
+        let mut response = Fetch::Request(request).send().await?;
+        response.text().await
+    };
+    let timed_out = async {
+        Delay::from(timeout).await;
+        Err(Error::RustError(format!(
+            "request to {url} timed out after {timeout:?}"
+        )))
+    };
+
+    futures::pin_mut!(fetch);
+    futures::pin_mut!(timed_out);
+    match futures::future::select(fetch, timed_out).await {
+        futures::future::Either::Left((result, _)) => result,
+        futures::future::Either::Right((result, _)) => result,
+    }
+}
+
+/// `base * 2^(attempt - 1)` plus up to `base` of random jitter.
+fn backoff_with_jitter(base: Duration, attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(31);
+    let backoff = base.saturating_mul(1u32 << exponent);
+    let jitter = Duration::from_millis(rand::thread_rng().gen_range(0..=base.as_millis() as u64));
+    backoff + jitter
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_with_jitter_is_bounded_between_backoff_and_backoff_plus_base() {
+        let base = Duration::from_millis(500);
+        for attempt in 1..=5 {
+            let exponent = attempt - 1;
+            let backoff = base * (1u32 << exponent);
+            let delay = backoff_with_jitter(base, attempt);
+            assert!(delay >= backoff, "attempt {attempt}: {delay:?} < {backoff:?}");
+            assert!(
+                delay <= backoff + base,
+                "attempt {attempt}: {delay:?} > {:?}",
+                backoff + base
+            );
+        }
+    }
+
+    #[test]
+    fn backoff_with_jitter_saturates_on_large_attempt_counts() {
+        // Should not overflow/panic even for an attempt count far beyond
+        // any realistic max_attempts.
+        let delay = backoff_with_jitter(Duration::from_millis(500), u32::MAX);
+        assert!(delay >= Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::workload::{Fixture, Workload};
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tracker_pipeline::{merge, parse_tracker};
+
+#[derive(Debug, Serialize)]
+struct FixtureReport {
+    fixture: String,
+    iterations: usize,
+    parse_total: Duration,
+    parse_throughput_per_sec: f64,
+    merge_total: Duration,
+    dedup_ratio: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct MockAria2Report {
+    url: String,
+    iterations: usize,
+    round_trip_total: Duration,
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    workload: String,
+    git_commit: String,
+    git_branch: String,
+    fixtures: Vec<FixtureReport>,
+    mock_aria2: Option<MockAria2Report>,
+}
+
+pub fn run(workload_path: &Path) -> anyhow::Result<()> {
+    let workload = Workload::load(workload_path)?;
+    let workload_dir = workload_path.parent().unwrap_or_else(|| Path::new("."));
+
+    let mut fixtures = Vec::with_capacity(workload.fixtures.len());
+    for fixture in &workload.fixtures {
+        fixtures.push(bench_fixture(fixture, workload_dir, workload.iterations)?);
+    }
+
+    let mock_aria2 = match &workload.mock_aria2_url {
+        Some(url) => Some(bench_mock_aria2(url, workload.iterations)?),
+        None => None,
+    };
+
+    let report = Report {
+        workload: workload.name.clone(),
+        git_commit: git_output(&["rev-parse", "HEAD"]),
+        git_branch: git_output(&["rev-parse", "--abbrev-ref", "HEAD"]),
+        fixtures,
+        mock_aria2,
+    };
+
+    std::fs::create_dir_all("reports")?;
+    let report_path = format!("reports/{}.json", workload.name);
+    std::fs::write(&report_path, serde_json::to_string_pretty(&report)?)?;
+    println!("Wrote {report_path}");
+
+    if let Some(dashboard_url) = &workload.dashboard_url {
+        post_report(dashboard_url, &report)?;
+    }
+
+    Ok(())
+}
+
+fn bench_fixture(
+    fixture: &Fixture,
+    workload_dir: &Path,
+    iterations: usize,
+) -> anyhow::Result<FixtureReport> {
+    let text = fixture.load(workload_dir)?;
+
+    let mut parse_total = Duration::ZERO;
+    let mut merge_total = Duration::ZERO;
+    let mut total_parsed = 0usize;
+    // Dedup ratio is a property of the fixture, not of how many times we
+    // re-run it, so it's captured from a single representative pass. Each
+    // iteration merges into its own fresh `DashSet` so `merge_total` keeps
+    // measuring real insertion cost instead of degenerating into no-op
+    // lookups once a shared set is already saturated.
+    let mut dedup_ratio = 0.0;
+    for i in 0..iterations {
+        let started = Instant::now();
+        let parsed = parse_tracker(&text)
+            .map_err(|err| anyhow::anyhow!("fixture {}: {err}", fixture.name()))?;
+        parse_total += started.elapsed();
+        let parsed_count = parsed.len();
+        total_parsed += parsed_count;
+
+        let merged = dashmap::DashSet::new();
+        let started = Instant::now();
+        merge(&merged, parsed);
+        merge_total += started.elapsed();
+
+        if i == 0 {
+            // `parse_tracker` already returns a set, so the pre-dedup entry
+            // count is taken from the fixture text with the same delimiters.
+            let raw_count = serde_json::from_str::<serde_json::Value>(&text)
+                .ok()
+                .and_then(|value| value.get("trackers")?.as_array().map(Vec::len))
+                .unwrap_or_else(|| {
+                    let delimiter = if text.contains(',') { "," } else { "\n\n" };
+                    text.split(delimiter).count()
+                });
+            if raw_count > 0 {
+                dedup_ratio = 1.0 - (merged.len() as f64 / raw_count as f64);
+            }
+        }
+    }
+
+    let parse_throughput_per_sec = if parse_total.as_secs_f64() > 0.0 {
+        total_parsed as f64 / parse_total.as_secs_f64()
+    } else {
+        0.0
+    };
+
+    Ok(FixtureReport {
+        fixture: fixture.name().to_string(),
+        iterations,
+        parse_total,
+        parse_throughput_per_sec,
+        merge_total,
+        dedup_ratio,
+    })
+}
+
+fn bench_mock_aria2(url: &str, iterations: usize) -> anyhow::Result<MockAria2Report> {
+    let client = reqwest::blocking::Client::new();
+    let payload = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "aria2.changeGlobalOption",
+        "id": "bench",
+        "params": ["token:bench", { "bt-tracker": "udp://example.test:80/announce" }]
+    });
+
+    let started = Instant::now();
+    for _ in 0..iterations {
+        let _ = client.post(url).json(&payload).send();
+    }
+    let round_trip_total = started.elapsed();
+
+    Ok(MockAria2Report {
+        url: url.to_string(),
+        iterations,
+        round_trip_total,
+    })
+}
+
+fn post_report(dashboard_url: &str, report: &Report) -> anyhow::Result<()> {
+    let client = reqwest::blocking::Client::new();
+    client.post(dashboard_url).json(report).send()?;
+    Ok(())
+}
+
+fn git_output(args: &[&str]) -> String {
+    Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|s| s.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
@@ -0,0 +1,35 @@
+//! Developer tooling for the tracker-collector crate. Currently only hosts
+//! the `bench` subcommand; run with `cargo xtask bench <workload.json>`.
+
+mod bench;
+mod workload;
+
+use std::path::PathBuf;
+
+enum Command {
+    Bench { workload: PathBuf },
+}
+
+fn parse_args() -> Command {
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("bench") => {
+            let workload = args
+                .next()
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("xtask/workloads/default.json"));
+            Command::Bench { workload }
+        }
+        other => {
+            eprintln!("usage: cargo xtask bench <workload.json>");
+            eprintln!("unknown subcommand: {other:?}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    match parse_args() {
+        Command::Bench { workload } => bench::run(&workload),
+    }
+}
@@ -0,0 +1,54 @@
+use serde::Deserialize;
+use std::path::Path;
+
+/// A single tracker-source fixture, either embedded inline or read from a
+/// file alongside the workload.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Fixture {
+    Inline { name: String, text: String },
+    File { name: String, path: String },
+}
+
+impl Fixture {
+    pub fn name(&self) -> &str {
+        match self {
+            Fixture::Inline { name, .. } => name,
+            Fixture::File { name, .. } => name,
+        }
+    }
+
+    /// Resolves the fixture to its text content, reading `File` fixtures
+    /// relative to the workload file's own directory.
+    pub fn load(&self, workload_dir: &Path) -> anyhow::Result<String> {
+        match self {
+            Fixture::Inline { text, .. } => Ok(text.clone()),
+            Fixture::File { path, .. } => {
+                let resolved = workload_dir.join(path);
+                Ok(std::fs::read_to_string(&resolved)?)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Workload {
+    pub name: String,
+    pub fixtures: Vec<Fixture>,
+    pub iterations: usize,
+    /// When set, also benchmarks a mock aria2 JSON-RPC round trip against
+    /// this local stub server URL.
+    #[serde(default)]
+    pub mock_aria2_url: Option<String>,
+    /// Optional dashboard endpoint to POST the report to, alongside writing
+    /// it to `reports/`.
+    #[serde(default)]
+    pub dashboard_url: Option<String>,
+}
+
+impl Workload {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&text)?)
+    }
+}